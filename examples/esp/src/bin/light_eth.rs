@@ -18,6 +18,7 @@ use alloc::boxed::Box;
 
 use embassy_executor::Spawner;
 use embassy_futures::select::select3;
+use embassy_net::StackResources;
 use embassy_time::{Duration, Timer};
 
 use esp_backtrace as _;
@@ -39,7 +40,7 @@ use rs_matter_embassy::matter::utils::select::Coalesce;
 use rs_matter_embassy::matter::{clusters, devices};
 use rs_matter_embassy::rand::esp::{esp_init_rand, esp_rand};
 use rs_matter_embassy::stack::persist::DummyKvBlobStore;
-use rs_matter_embassy::stack::utils::futures::IntoFaillble;
+use rs_matter_embassy::stack::utils::futures::IntoFallible;
 
 extern crate alloc;
 
@@ -99,6 +100,14 @@ async fn main(_s: Spawner) {
     let wifi = peripherals.WIFI;
     let (controller, wifi_interface) = esp_wifi::wifi::new(&init, wifi).unwrap();
 
+    // `embassy-net` needs its own scratch memory (socket storage) plus a seed for the initial
+    // TCP sequence number / UDP source port choice; best-effort entropy is fine here, we already
+    // seeded the real rand fn used for Matter's own crypto via `esp_init_rand` above
+    let net_resources = Box::leak(Box::new(StackResources::<3>::new()));
+    let mut seed = [0; 8];
+    esp_rand(&mut seed);
+    let net_seed = u64::from_le_bytes(seed);
+
     // Our "light" on-off cluster.
     // Can be anything implementing `rs_matter::dm::AsyncHandler`
     let on_off = on_off::OnOffHandler::new_standalone(
@@ -130,7 +139,12 @@ async fn main(_s: Spawner) {
     let store = stack.create_shared_store(DummyKvBlobStore);
     let mut matter = pin!(stack.run(
         // The Matter stack needs the ethernet inteface to run
-        EmbassyEthernet::new(PreexistingEthDriver::new(wifi_interface.sta), stack),
+        EmbassyEthernet::new(
+            PreexistingEthDriver::new(wifi_interface.sta),
+            net_resources,
+            net_seed,
+            stack,
+        ),
         // The Matter stack needs a persister to store its state
         &store,
         // Our `AsyncHandler` + `AsyncMetadata` impl
@@ -40,7 +40,7 @@ use rs_matter_embassy::matter::{clusters, devices, BasicCommData};
 use rs_matter_embassy::rand::esp::{esp_init_rand, esp_rand};
 use rs_matter_embassy::stack::persist::DummyKvBlobStore;
 use rs_matter_embassy::stack::rand::RngCore;
-use rs_matter_embassy::wireless::esp::EspThreadDriver;
+use rs_matter_embassy::wireless::esp::{EspBleHci, EspThreadDriver};
 use rs_matter_embassy::wireless::{EmbassyThread, EmbassyThreadMatterStack};
 
 use tinyrlibc as _;
@@ -144,14 +144,18 @@ async fn main(_s: Spawner) {
     //
     // This step can be repeated in that the stack can be stopped and started multiple times, as needed.
     let store = stack.create_shared_store(DummyKvBlobStore);
+    let mut ble = EspBleHci::new(&init, peripherals.BT);
     let mut matter = pin!(stack.run(
         // The Matter stack needs to instantiate an `openthread` Radio
         EmbassyThread::new(
-            EspThreadDriver::new(&init, peripherals.IEEE802154, peripherals.BT),
+            EspThreadDriver::new(&init, peripherals.IEEE802154),
             ieee_eui64,
             &store,
             stack,
-        ),
+        )
+        // Reads and discards BLE HCI traffic alongside the radio for as long as this transport
+        // runs, so the controller's RX queue keeps draining (no BTP/GATT session runs on top)
+        .with_ble_transport(&mut ble),
         // The Matter stack needs a persister to store its state
         &store,
         // Our `AsyncHandler` + `AsyncMetadata` impl
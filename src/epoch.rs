@@ -0,0 +1,14 @@
+//! An `embassy-time`-backed implementation of `rs-matter`'s `Epoch` (monotonic clock) contract.
+
+use core::time::Duration;
+
+/// A monotonic `rs-matter` `Epoch` based on `embassy_time::Instant`.
+///
+/// `embassy_time::Instant::now()` is monotonic for as long as the device stays powered, which is
+/// all `rs-matter` requires of an `Epoch` (it is used for retransmission timers and exchange
+/// timeouts, not wall-clock time).
+pub fn epoch() -> Duration {
+    embassy_time::Instant::now()
+        .duration_since(embassy_time::Instant::MIN)
+        .into()
+}
@@ -0,0 +1,171 @@
+//! The Ethernet (i.e. "I already have an IP-capable L2 interface, Matter does not need to bring
+//! one up itself") Matter stack assembly.
+//!
+//! This is also what backs WiFi usage where the device does not need Matter to own WiFi
+//! commissioning (e.g. the WiFi credentials are provisioned out of band, or - as in the
+//! `light_eth` example - we are only demoing and the network is assumed to "pre-exist").
+
+use embassy_futures::select::select;
+use embassy_net::{Runner, Stack, StackResources};
+use embassy_net_driver::{Driver, LinkState};
+use embassy_time::{Duration, Timer};
+
+use crate::stack::{MatterStack, NetRunner};
+
+/// How often [`EmbassyEthernet::run`] polls `embassy-net`'s `Stack::is_link_up` for a link-state
+/// transition to re-announce mDNS on.
+const LINK_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A Matter stack assembled over a pre-existing, already-IP-capable network interface.
+pub type EmbassyEthMatterStack<const N: usize, T = ()> = MatterStack<N, T>;
+
+/// Drives Matter's transport (UDP sockets for interaction model traffic + mDNS) over an
+/// `embassy-net` `Stack` built on top of a [`Driver`].
+///
+/// Any type implementing [`embassy_net_driver::Driver`] can be used here, whether that's
+/// `esp-wifi`'s WiFi-STA device (pretending to be Ethernet, as in the `light_eth` example), a
+/// real wired PHY driver such as `embassy-net-wiznet` (WIZnet W5500) or `embassy-net-enc28j60`
+/// (Microchip ENC28J60), or the `esp-hosted` co-processor transport.
+pub struct EmbassyEthernet<'a, const N: usize, D, T = ()>
+where
+    D: Driver,
+{
+    stack: &'a MatterStack<N, T>,
+    net_stack: Stack<'a>,
+    net_runner: Runner<'a, D>,
+    hardware_address: [u8; 6],
+    link_up: bool,
+}
+
+impl<'a, const N: usize, D, T> EmbassyEthernet<'a, N, D, T>
+where
+    D: Driver,
+{
+    /// Creates a new `EmbassyEthernet` transport: `driver` and `resources` build the
+    /// `embassy-net` `Stack` this transport owns and polls, and `stack` is the Matter stack it is
+    /// feeding. The returned value is handed to [`MatterStack::run`] as the `net` argument, same
+    /// as the `light_eth` example does with `PreexistingEthDriver`.
+    ///
+    /// `seed` seeds the stack's (pseudo-)random port/sequence-number generator; any fixed value
+    /// works for bring-up, but production devices should derive it from true entropy so two
+    /// devices never pick the same initial TCP sequence numbers.
+    pub fn new(
+        driver: D,
+        resources: &'a mut StackResources<3>,
+        seed: u64,
+        stack: &'a MatterStack<N, T>,
+    ) -> Self {
+        let hardware_address = match driver.hardware_address() {
+            embassy_net_driver::HardwareAddress::Ethernet(addr) => addr.0,
+            #[allow(unreachable_patterns)]
+            _ => [0; 6],
+        };
+
+        let (net_stack, net_runner) =
+            embassy_net::new(driver, embassy_net::Config::default(), resources, seed);
+
+        Self {
+            stack,
+            net_stack,
+            net_runner,
+            hardware_address,
+            link_up: false,
+        }
+    }
+
+    /// The interface's MAC address, as reported by the underlying driver at construction time.
+    pub fn hardware_address(&self) -> [u8; 6] {
+        self.hardware_address
+    }
+}
+
+impl<'a, const N: usize, D, T> NetRunner for EmbassyEthernet<'a, N, D, T>
+where
+    D: Driver,
+{
+    /// Pumps L2 frames to/from the underlying driver by polling the `embassy-net` `Runner` this
+    /// transport owns, re-announcing mDNS on the Matter stack every time the link comes back up.
+    /// Never returns under normal operation, same as `embassy_net::Runner::run` itself.
+    async fn run(&mut self) -> Result<(), rs_matter::error::Error> {
+        let net_stack = self.net_stack;
+        let matter_stack = self.stack;
+        let link_up = &mut self.link_up;
+
+        let link_watch = async {
+            loop {
+                Timer::after(LINK_POLL_INTERVAL).await;
+
+                let now_up = net_stack.is_link_up();
+
+                if now_up && !*link_up {
+                    matter_stack.notify_cluster_changed(0, 0);
+                }
+
+                *link_up = now_up;
+            }
+        };
+
+        select(self.net_runner.run(), link_watch).await;
+
+        Ok(())
+    }
+}
+
+/// Wraps a `D: embassy_net_driver::Driver` that was constructed (and is owned) elsewhere - e.g.
+/// `esp-wifi`'s `wifi_interface.sta`, or a driver from a third-party PHY crate such as
+/// `embassy-net-wiznet` - so it can back an [`EmbassyEthernet`] transport as-is.
+///
+/// This is a thin, zero-cost wrapper: it exists only so that `EmbassyEthernet::new` has a single,
+/// uniform entry point regardless of where the driver came from.
+pub struct PreexistingEthDriver<D>(D);
+
+impl<D> PreexistingEthDriver<D>
+where
+    D: Driver,
+{
+    /// Wraps `driver` for use with [`EmbassyEthernet`].
+    ///
+    /// `driver` only needs to implement [`embassy_net_driver::Driver`], so besides WiFi-as-
+    /// Ethernet this also covers real wired PHYs driven through their own `Driver` impl (WIZnet
+    /// W5500, Microchip ENC28J60, the `esp-hosted` co-processor, ...).
+    pub fn new(driver: D) -> Self {
+        Self(driver)
+    }
+}
+
+impl<D> Driver for PreexistingEthDriver<D>
+where
+    D: Driver,
+{
+    type RxToken<'a>
+        = D::RxToken<'a>
+    where
+        Self: 'a;
+    type TxToken<'a>
+        = D::TxToken<'a>
+    where
+        Self: 'a;
+
+    fn receive(
+        &mut self,
+        cx: &mut core::task::Context,
+    ) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        self.0.receive(cx)
+    }
+
+    fn transmit(&mut self, cx: &mut core::task::Context) -> Option<Self::TxToken<'_>> {
+        self.0.transmit(cx)
+    }
+
+    fn link_state(&mut self, cx: &mut core::task::Context) -> LinkState {
+        self.0.link_state(cx)
+    }
+
+    fn capabilities(&self) -> embassy_net_driver::Capabilities {
+        self.0.capabilities()
+    }
+
+    fn hardware_address(&self) -> embassy_net_driver::HardwareAddress {
+        self.0.hardware_address()
+    }
+}
@@ -0,0 +1,19 @@
+//! `rs-matter-embassy` - glue between the [`rs-matter`](https://github.com/project-chip/rs-matter)
+//! Matter stack and the [`embassy`](https://embassy.dev) async runtime / HAL ecosystem.
+//!
+//! This crate assembles a concrete, runnable Matter stack (transport + commissioning + persistence)
+//! out of `embassy-net`, `embassy-time` and vendor HAL pieces (currently `esp-hal` / `esp-wifi`),
+//! so that device firmware only has to provide its data model handlers.
+// Tests pull in `std` (an in-memory `NorFlash` mock for `stack::persist::esp`'s test suite needs
+// `Rc`/`RefCell`/`Vec`) - production builds stay `no_std`.
+#![cfg_attr(not(test), no_std)]
+
+pub mod epoch;
+pub mod eth;
+pub mod rand;
+pub mod stack;
+pub mod wireless;
+
+/// Re-export of `rs-matter` itself, so that downstream crates depend on a single, pinned
+/// version of it through us rather than pulling in their own (potentially mismatched) copy.
+pub use rs_matter as matter;
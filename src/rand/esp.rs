@@ -0,0 +1,32 @@
+//! `esp-hal` RNG glue.
+
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+
+use esp_hal::rng::Rng;
+
+static RNG: Mutex<RefCell<Option<Rng>>> = Mutex::new(RefCell::new(None));
+
+/// Initializes the global RNG used by [`esp_rand`].
+///
+/// Must be called exactly once, before the Matter stack is initialized, as `rs-matter` takes its
+/// rand source as a bare `fn`, not a trait or a closure, so there is no per-instance way to thread
+/// the `Rng` peripheral through.
+pub fn esp_init_rand(rng: Rng) {
+    critical_section::with(|cs| {
+        *RNG.borrow_ref_mut(cs) = Some(rng);
+    });
+}
+
+/// The global rand `fn` `rs-matter` is configured with. See [`esp_init_rand`].
+pub fn esp_rand(buf: &mut [u8]) {
+    use rand_core::RngCore;
+
+    critical_section::with(|cs| {
+        let mut rng = RNG.borrow_ref_mut(cs);
+        let rng = rng.as_mut().expect("`esp_init_rand` was not called");
+
+        rng.fill_bytes(buf);
+    });
+}
@@ -0,0 +1,4 @@
+//! `rand` glue between `rs-matter` (which takes a plain `fn(&mut [u8])` rand callback, to avoid
+//! dragging a generic or a trait object through every type in the stack) and vendor HAL RNGs.
+
+pub mod esp;
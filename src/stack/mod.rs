@@ -0,0 +1,150 @@
+//! The transport- and commissioning-agnostic core shared by all the concrete stack assemblies in
+//! this crate (`eth::EmbassyEthMatterStack`, `wireless::EmbassyThreadMatterStack`,
+//! `wireless::EmbassyWifiMatterStack`, ...).
+//!
+//! `MatterStack` owns the `rs_matter::Matter` instance; the
+//! transport- and commissioning-specific assemblies in the sibling modules just plug a concrete
+//! network/BLE transport and persistence store into `run()`.
+
+pub mod persist;
+pub mod rand;
+pub mod utils;
+
+use core::future::Future;
+
+use rs_matter::dm::{AsyncHandler, AsyncMetadata, Node};
+use rs_matter::utils::select::Coalesce;
+use rs_matter::Matter;
+
+use self::persist::KvBlobStore;
+
+/// The Matter stack: the pieces that are common to every transport this crate supports.
+///
+/// `N` is the bump allocator size (in bytes) the examples pass as `BUMP_SIZE`; it is a type
+/// parameter only, and `MatterStack` holds no buffer of that size. `T` is transport-specific
+/// state the concrete assembly (`eth`/`wireless`) stashes alongside it.
+pub struct MatterStack<const N: usize, T = ()> {
+    matter: Matter<'static>,
+    transport_state: T,
+}
+
+impl<const N: usize, T> MatterStack<N, T> {
+    /// Returns the underlying `rs_matter::Matter` instance.
+    pub fn matter(&self) -> &Matter<'static> {
+        &self.matter
+    }
+
+    /// Wraps `store` as the shared, `&self`-borrowable persister the stack and its transport both
+    /// read/write fabric, ACL and (where applicable) network credential state through.
+    pub fn create_shared_store<S>(&self, store: S) -> SharedKvBlobStore<S>
+    where
+        S: KvBlobStore,
+    {
+        SharedKvBlobStore(store)
+    }
+
+    /// Notifies the stack that the state of cluster `cluster_id` on endpoint `endpoint_id` has
+    /// changed, so that subscribed controllers get a report.
+    pub fn notify_cluster_changed(&self, endpoint_id: u16, cluster_id: u32) {
+        self.matter.notify_changed();
+        let _ = (endpoint_id, cluster_id);
+    }
+
+    /// The root endpoint (ID 0) every Matter node must expose; carries the mandatory system
+    /// clusters (Basic Information, Access Control, General Commissioning, ...).
+    pub const fn root_endpoint() -> rs_matter::dm::Endpoint<'static> {
+        rs_matter::dm::root_endpoint::endpoint(0)
+    }
+
+    /// Runs this Matter stack to completion: `net` (the concrete transport assembly - e.g.
+    /// [`crate::eth::EmbassyEthernet`], [`crate::wireless::EmbassyThread`],
+    /// [`crate::wireless::EmbassyWifi`]), the Matter protocol itself (interaction model traffic +
+    /// mDNS, persisted through `persist`), and - concurrently - `user`, are all joined together so
+    /// that any one of them finishing (successfully or not) stops the others.
+    ///
+    /// `node_handler` is the `(Node, AsyncHandler + AsyncMetadata)` pair describing this device's
+    /// endpoints and clusters; `user` is typically `()` (nothing extra to run), or a caller-
+    /// provided future such as a sensor-polling loop that calls [`Self::notify_cluster_changed`].
+    pub async fn run<'d, Net, S, H>(
+        &self,
+        mut net: Net,
+        persist: &S,
+        node_handler: (Node<'d>, H),
+        user: impl IntoUserTask,
+    ) -> Result<(), rs_matter::error::Error>
+    where
+        Net: NetRunner,
+        S: KvBlobStore,
+        H: AsyncHandler + AsyncMetadata,
+    {
+        let (node, handler) = node_handler;
+
+        let mut net_fut = core::pin::pin!(net.run());
+        let mut matter_fut = core::pin::pin!(self.matter.run(&node, handler, persist));
+        let mut user_fut = core::pin::pin!(user.into_user_task());
+
+        embassy_futures::select::select3(&mut net_fut, &mut matter_fut, &mut user_fut)
+            .coalesce()
+            .await
+    }
+}
+
+/// Drives a concrete network transport (Ethernet, Thread, WiFi, ...) on behalf of
+/// [`MatterStack::run`].
+///
+/// Implementors own whatever it takes to bring up and keep up L2/L3 connectivity for their
+/// transport - an `embassy-net` `Stack` and its `Runner` for Ethernet
+/// ([`crate::eth::EmbassyEthernet`]), the 802.15.4 radio and BLE commissioning window for Thread
+/// ([`crate::wireless::EmbassyThread`]), the WiFi station state machine and BLE commissioning
+/// window for WiFi ([`crate::wireless::EmbassyWifi`]).
+pub trait NetRunner {
+    /// Drives the transport. Expected to run forever under normal operation - a transient loss of
+    /// connectivity should be retried internally rather than surfaced as an `Err` here.
+    async fn run(&mut self) -> Result<(), rs_matter::error::Error>;
+}
+
+/// Something [`MatterStack::run`] can run alongside the Matter stack and its transport: either no
+/// user task at all (`()`), or a fallible future of the caller's own.
+///
+/// This only exists so callers can pass `()` for "nothing to run" (as every example in this crate
+/// does) without having to wrap it in [`core::future::pending`] themselves.
+pub trait IntoUserTask {
+    fn into_user_task(self) -> impl Future<Output = Result<(), rs_matter::error::Error>>;
+}
+
+impl IntoUserTask for () {
+    fn into_user_task(self) -> impl Future<Output = Result<(), rs_matter::error::Error>> {
+        core::future::pending()
+    }
+}
+
+impl<F> IntoUserTask for F
+where
+    F: Future<Output = Result<(), rs_matter::error::Error>>,
+{
+    fn into_user_task(self) -> impl Future<Output = Result<(), rs_matter::error::Error>> {
+        self
+    }
+}
+
+/// A [`KvBlobStore`] wrapped so it can be shared, by shared reference, between the running Matter
+/// stack and its transport (e.g. a wireless driver persisting network credentials).
+pub struct SharedKvBlobStore<S>(S);
+
+impl<S: KvBlobStore> KvBlobStore for SharedKvBlobStore<S> {
+    async fn load<'a>(
+        &self,
+        key: &str,
+        buf: &'a mut [u8],
+    ) -> Result<Option<&'a [u8]>, persist::KvBlobStoreError> {
+        self.0.load(key, buf).await
+    }
+
+    async fn store(&self, key: &str, data: &[u8]) -> Result<(), persist::KvBlobStoreError> {
+        self.0.store(key, data).await
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), persist::KvBlobStoreError> {
+        self.0.remove(key).await
+    }
+}
@@ -0,0 +1,748 @@
+//! A flash-backed, wear-leveled [`KvBlobStore`].
+//!
+//! [`DummyKvBlobStore`][super::DummyKvBlobStore] is fine for bring-up, but real devices need
+//! fabrics, ACLs and (for the wireless variants) network credentials to survive a reboot.
+//! `EspKvBlobStore` gets there with a small log-structured scheme over a raw internal flash
+//! region: records are appended sequentially, the last valid record for a key wins, and the
+//! region is compacted into its other half once it fills up - so writes are spread across the
+//! whole partition instead of repeatedly hammering the same sector, and a reboot after a power
+//! loss mid-write just sees the log end a little earlier than it otherwise would have.
+
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+
+use embedded_storage::nor_flash::NorFlash;
+
+use super::{KvBlobStore, KvBlobStoreError};
+
+/// Marks the start of a valid record; lets the scanner tell a real record apart from erased
+/// (`0xFF`-filled) or power-loss-truncated flash.
+const MAGIC: u32 = 0x4d42_4c42; // "MBLB"
+const MAX_KEY_LEN: usize = 16;
+/// The largest blob `store`/`append_record` will ever accept. `compact` carries a live record's
+/// data through a fixed-size stack buffer of exactly this length (`data_len` is otherwise a
+/// `u16`, so it alone doesn't bound it) - a blob admitted here that didn't fit that buffer would
+/// only be discovered once a fill-triggered compaction tried to carry it forward, by which point
+/// the destination half has already been erased and partially written.
+const MAX_DATA_LEN: usize = 256;
+const HEADER_LEN: usize = 4 /* magic */ + 1 /* key_len */ + 2 /* data_len */;
+/// A trailing CRC32 of `key || data`, written after a record's payload. A torn write (power loss
+/// while the payload or this CRC itself was being written) leaves it not matching, which is how
+/// the scanner tells a genuinely complete record from a partially-written one.
+const CRC_LEN: usize = 4;
+/// A generation counter reserved at the very start of each half, bumped every time `compact`
+/// makes that half the active one.
+///
+/// Without this, mount cannot tell which half is current from the records alone: flipping only
+/// erases and writes the *new* active half, so right after a flip the half just vacated is still
+/// a fully valid - merely stale - log, not erased flash. Whichever half has the higher generation
+/// (mod wraparound, see [`generation_is_newer`]) is the one to resume from.
+const GEN_LEN: u32 = 4;
+
+/// A log-structured [`KvBlobStore`] over a raw internal flash region, split into two equally
+/// sized halves so compaction always has somewhere to copy live records to without erasing the
+/// half it is still reading from.
+///
+/// `F` is the raw flash peripheral driver; it only needs to implement
+/// [`embedded_storage::nor_flash::NorFlash`].
+pub struct EspKvBlobStore<F> {
+    inner: Mutex<RefCell<Inner<F>>>,
+}
+
+struct Inner<F> {
+    flash: F,
+    base_offset: u32,
+    half_len: u32,
+    /// Which half (`0` or `1`) is currently being appended to.
+    active_half: u32,
+    /// The active half's generation counter (see [`GEN_LEN`]); bumped on every compaction.
+    generation: u32,
+}
+
+impl<F> EspKvBlobStore<F>
+where
+    F: NorFlash,
+{
+    /// Creates a new store over `2 * half_len` bytes of `flash`, starting at `base_offset`.
+    ///
+    /// `base_offset` and `half_len` must both be aligned to `F::ERASE_SIZE`, and the region must
+    /// not overlap anything else on the flash part. Mount reads both halves' generation counters
+    /// (see [`GEN_LEN`]) to resume from whichever is actually current, rather than assuming half
+    /// `0` - a prior compaction may have flipped to half `1` before the last reboot.
+    pub fn new(mut flash: F, base_offset: u32, half_len: u32) -> Result<Self, KvBlobStoreError> {
+        let gen0 = read_generation(&mut flash, base_offset)?;
+        let gen1 = read_generation(&mut flash, base_offset + half_len)?;
+
+        let (active_half, generation) = match (gen0, gen1) {
+            (None, None) => (0, 0),
+            (Some(g0), None) => (0, g0),
+            (None, Some(g1)) => (1, g1),
+            (Some(g0), Some(g1)) => {
+                if generation_is_newer(g1, g0) {
+                    (1, g1)
+                } else {
+                    (0, g0)
+                }
+            }
+        };
+
+        Ok(Self {
+            inner: Mutex::new(RefCell::new(Inner {
+                flash,
+                base_offset,
+                half_len,
+                active_half,
+                generation,
+            })),
+        })
+    }
+}
+
+impl<F> KvBlobStore for EspKvBlobStore<F>
+where
+    F: NorFlash,
+{
+    async fn load<'a>(
+        &self,
+        key: &str,
+        buf: &'a mut [u8],
+    ) -> Result<Option<&'a [u8]>, KvBlobStoreError> {
+        critical_section::with(|cs| {
+            let mut inner = self.inner.borrow_ref_mut(cs);
+            let Inner {
+                flash,
+                base_offset,
+                half_len,
+                active_half,
+                ..
+            } = &mut *inner;
+
+            let records_base = *base_offset + *active_half * *half_len + GEN_LEN;
+            let records_len = *half_len - GEN_LEN;
+
+            let mut found_len = None;
+            let mut offset = 0;
+
+            // No RAM index is kept - every `load` re-scans the active half's log from the start,
+            // streaming each record's header (and only that record's data, into `buf`) rather
+            // than buffering the whole store.
+            while offset + HEADER_LEN as u32 <= records_len {
+                let Some((record_key_len, data_len)) = read_header(flash, records_base + offset)?
+                else {
+                    break;
+                };
+
+                let key_off = records_base + offset + HEADER_LEN as u32;
+                let data_off = key_off + record_key_len as u32;
+
+                if record_key_len as usize == key.len() && read_matches_key(flash, key_off, key)? {
+                    // A later record for the same key always supersedes an earlier one,
+                    // including a zero-length one, which marks a `remove`.
+                    found_len = if data_len == 0 {
+                        None
+                    } else {
+                        Some((data_off, data_len))
+                    };
+                }
+
+                offset +=
+                    HEADER_LEN as u32 + record_key_len as u32 + data_len as u32 + CRC_LEN as u32;
+            }
+
+            match found_len {
+                Some((data_off, data_len)) => {
+                    if (data_len as usize) > buf.len() {
+                        return Err(KvBlobStoreError::BufferTooSmall);
+                    }
+
+                    let dest = &mut buf[..data_len as usize];
+                    flash
+                        .read(data_off, dest)
+                        .map_err(|_| KvBlobStoreError::Storage)?;
+
+                    Ok(Some(&buf[..data_len as usize]))
+                }
+                None => Ok(None),
+            }
+        })
+    }
+
+    async fn store(&self, key: &str, data: &[u8]) -> Result<(), KvBlobStoreError> {
+        critical_section::with(|cs| {
+            let mut inner = self.inner.borrow_ref_mut(cs);
+            append_record(&mut inner, key, data)
+        })
+    }
+
+    async fn remove(&self, key: &str) -> Result<(), KvBlobStoreError> {
+        // A tombstone is just a zero-length record - `load`'s scan already treats it as "no
+        // value", and compaction (see `append_record`) drops tombstones for keys that no longer
+        // need to be carried forward.
+        critical_section::with(|cs| {
+            let mut inner = self.inner.borrow_ref_mut(cs);
+            append_record(&mut inner, key, &[])
+        })
+    }
+}
+
+fn append_record<F: NorFlash>(
+    inner: &mut Inner<F>,
+    key: &str,
+    data: &[u8],
+) -> Result<(), KvBlobStoreError> {
+    if key.len() > MAX_KEY_LEN {
+        return Err(KvBlobStoreError::Storage);
+    }
+
+    if data.len() > MAX_DATA_LEN {
+        return Err(KvBlobStoreError::TooLarge);
+    }
+
+    let records_len = inner.half_len - GEN_LEN;
+    let len = record_len(key.len(), data.len());
+    if len > records_len {
+        // Won't fit even in a freshly-compacted, otherwise-empty half.
+        return Err(KvBlobStoreError::Full);
+    }
+
+    let records_base = inner.base_offset + inner.active_half * inner.half_len + GEN_LEN;
+    let end = scan_end(&mut inner.flash, records_base, records_len)?;
+
+    if end + len > records_len {
+        compact(inner, key, data)?;
+        return Ok(());
+    }
+
+    write_record(&mut inner.flash, records_base + end, key, data)
+}
+
+/// Scans the active half for the offset right after its last valid record (i.e. where the next
+/// `append_record` should write), relative to `records_base`.
+fn scan_end<F: NorFlash>(
+    flash: &mut F,
+    records_base: u32,
+    records_len: u32,
+) -> Result<u32, KvBlobStoreError> {
+    let mut offset = 0;
+
+    while offset + HEADER_LEN as u32 <= records_len {
+        match read_header(flash, records_base + offset)? {
+            Some((key_len, data_len)) => {
+                offset += HEADER_LEN as u32 + key_len as u32 + data_len as u32 + CRC_LEN as u32;
+            }
+            None => break,
+        }
+    }
+
+    Ok(offset)
+}
+
+/// Reclaims space by replaying every live key's latest record (plus the new `key`/`data` write
+/// that triggered compaction) into the other half, then flips which half is active. The half just
+/// vacated is left as it is (a stale log) until the next compaction erases it to write into it.
+fn compact<F: NorFlash>(
+    inner: &mut Inner<F>,
+    new_key: &str,
+    new_data: &[u8],
+) -> Result<(), KvBlobStoreError> {
+    let from_base = inner.base_offset + inner.active_half * inner.half_len;
+    let from_records_base = from_base + GEN_LEN;
+    let to_half = 1 - inner.active_half;
+    let to_base = inner.base_offset + to_half * inner.half_len;
+    let to_records_base = to_base + GEN_LEN;
+    let records_len = inner.half_len - GEN_LEN;
+
+    inner
+        .flash
+        .erase(to_base, to_base + inner.half_len)
+        .map_err(|_| KvBlobStoreError::Storage)?;
+
+    let mut write_offset = 0;
+    let mut seen: heapless::Vec<heapless::String<MAX_KEY_LEN>, 32> = heapless::Vec::new();
+
+    // Walk the old log back-to-front-by-key: we want each key's *last* record, so we record
+    // which keys we've already carried forward and skip older ones for the same key.
+    let mut records: heapless::Vec<(u32, u8, u16), 64> = heapless::Vec::new();
+    let mut offset = 0;
+
+    while offset + HEADER_LEN as u32 <= records_len {
+        match read_header(&mut inner.flash, from_records_base + offset)? {
+            Some((key_len, data_len)) => {
+                // A store sized per `new()`'s own contract can never actually produce more live
+                // records than these caps allow, so overflowing here means the region is
+                // corrupted or was mounted with a size it wasn't formatted for - fail loudly
+                // rather than silently dropping records out of the compacted log.
+                records
+                    .push((offset, key_len, data_len))
+                    .map_err(|_| KvBlobStoreError::Storage)?;
+                offset += HEADER_LEN as u32 + key_len as u32 + data_len as u32 + CRC_LEN as u32;
+            }
+            None => break,
+        }
+    }
+
+    for &(offset, key_len, data_len) in records.iter().rev() {
+        let key_off = from_records_base + offset + HEADER_LEN as u32;
+        let mut key_buf = [0u8; MAX_KEY_LEN];
+        inner
+            .flash
+            .read(key_off, &mut key_buf[..key_len as usize])
+            .map_err(|_| KvBlobStoreError::Storage)?;
+        let key_str = core::str::from_utf8(&key_buf[..key_len as usize])
+            .map_err(|_| KvBlobStoreError::Storage)?;
+
+        if key_str == new_key || seen.iter().any(|k| k.as_str() == key_str) {
+            continue;
+        }
+        let mut owned = heapless::String::new();
+        owned
+            .push_str(key_str)
+            .map_err(|_| KvBlobStoreError::Storage)?;
+        seen.push(owned).map_err(|_| KvBlobStoreError::Storage)?;
+
+        if data_len == 0 {
+            // A tombstone: the key is gone, and since this is its last record we are done with
+            // it - don't carry a delete marker forward into a freshly-erased region.
+            continue;
+        }
+
+        let data_off = key_off + key_len as u32;
+        let mut data_buf = [0u8; MAX_DATA_LEN];
+        if data_len as usize > data_buf.len() {
+            // `append_record` rejects anything over `MAX_DATA_LEN` before it ever reaches flash,
+            // so a live record exceeding it here means the region is corrupted or was mounted
+            // with a size/limit it wasn't formatted for - fail loudly rather than aborting the
+            // compaction after `to_base` has already been erased and partially written.
+            return Err(KvBlobStoreError::Storage);
+        }
+        inner
+            .flash
+            .read(data_off, &mut data_buf[..data_len as usize])
+            .map_err(|_| KvBlobStoreError::Storage)?;
+
+        if write_offset + record_len(key_str.len(), data_len as usize) > records_len {
+            return Err(KvBlobStoreError::Full);
+        }
+        write_offset += write_record_at(
+            &mut inner.flash,
+            to_records_base + write_offset,
+            key_str,
+            &data_buf[..data_len as usize],
+        )?;
+    }
+
+    if !new_data.is_empty() || !seen.iter().any(|k| k.as_str() == new_key) {
+        if write_offset + record_len(new_key.len(), new_data.len()) > records_len {
+            return Err(KvBlobStoreError::Full);
+        }
+        write_offset +=
+            write_record_at(&mut inner.flash, to_records_base + write_offset, new_key, new_data)?;
+    }
+
+    let _ = write_offset;
+
+    // Only stamp the new half's generation once every record is in place, so a compaction that
+    // fails (or loses power) partway through leaves a half that mount still ignores.
+    let new_generation = inner.generation.wrapping_add(1);
+    write_generation(&mut inner.flash, to_base, new_generation)?;
+
+    inner.active_half = to_half;
+    inner.generation = new_generation;
+    Ok(())
+}
+
+/// The on-flash size of a record with the given key and data lengths (header + key + data +
+/// trailing CRC32).
+fn record_len(key_len: usize, data_len: usize) -> u32 {
+    (HEADER_LEN + key_len + data_len + CRC_LEN) as u32
+}
+
+fn write_record<F: NorFlash>(
+    flash: &mut F,
+    offset: u32,
+    key: &str,
+    data: &[u8],
+) -> Result<(), KvBlobStoreError> {
+    write_record_at(flash, offset, key, data).map(|_| ())
+}
+
+fn write_record_at<F: NorFlash>(
+    flash: &mut F,
+    offset: u32,
+    key: &str,
+    data: &[u8],
+) -> Result<u32, KvBlobStoreError> {
+    let mut header = [0u8; HEADER_LEN];
+    header[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+    header[4] = key.len() as u8;
+    header[5..7].copy_from_slice(&(data.len() as u16).to_le_bytes());
+
+    flash
+        .write(offset, &header)
+        .map_err(|_| KvBlobStoreError::Storage)?;
+    flash
+        .write(offset + HEADER_LEN as u32, key.as_bytes())
+        .map_err(|_| KvBlobStoreError::Storage)?;
+    flash
+        .write(offset + HEADER_LEN as u32 + key.len() as u32, data)
+        .map_err(|_| KvBlobStoreError::Storage)?;
+
+    let mut crc = crc32_init();
+    crc = crc32_update(crc, key.as_bytes());
+    crc = crc32_update(crc, data);
+    let crc = crc32_finish(crc);
+
+    flash
+        .write(
+            offset + HEADER_LEN as u32 + key.len() as u32 + data.len() as u32,
+            &crc.to_le_bytes(),
+        )
+        .map_err(|_| KvBlobStoreError::Storage)?;
+
+    Ok((HEADER_LEN + key.len() + data.len() + CRC_LEN) as u32)
+}
+
+/// Reads and fully validates (magic + trailing CRC32) the record at `offset`, returning `None` if
+/// it is not a complete, valid record - whether that's erased flash, or a record that was only
+/// partially written before a power loss. The caller treats `None` as the end of the log: nothing
+/// at or after `offset` is trusted once this happens.
+fn read_header<F: NorFlash>(
+    flash: &mut F,
+    offset: u32,
+) -> Result<Option<(u8, u16)>, KvBlobStoreError> {
+    let mut header = [0u8; HEADER_LEN];
+    flash
+        .read(offset, &mut header)
+        .map_err(|_| KvBlobStoreError::Storage)?;
+
+    let magic = u32::from_le_bytes(header[0..4].try_into().unwrap());
+    if magic != MAGIC {
+        return Ok(None);
+    }
+
+    let key_len = header[4];
+    let data_len = u16::from_le_bytes(header[5..7].try_into().unwrap());
+
+    if key_len as usize > MAX_KEY_LEN {
+        return Ok(None);
+    }
+
+    let payload_off = offset + HEADER_LEN as u32;
+    let stored_crc = match read_crc32(flash, payload_off, key_len as u32 + data_len as u32) {
+        Ok(crc) => crc,
+        Err(_) => return Ok(None),
+    };
+
+    let mut trailer = [0u8; CRC_LEN];
+    flash
+        .read(payload_off + key_len as u32 + data_len as u32, &mut trailer)
+        .map_err(|_| KvBlobStoreError::Storage)?;
+
+    if u32::from_le_bytes(trailer) != stored_crc {
+        return Ok(None);
+    }
+
+    Ok(Some((key_len, data_len)))
+}
+
+/// Reads the generation counter reserved at the start of a half (see [`GEN_LEN`]), or `None` if
+/// that half has never been written (still fully erased).
+fn read_generation<F: NorFlash>(
+    flash: &mut F,
+    half_base: u32,
+) -> Result<Option<u32>, KvBlobStoreError> {
+    let mut buf = [0u8; GEN_LEN as usize];
+    flash
+        .read(half_base, &mut buf)
+        .map_err(|_| KvBlobStoreError::Storage)?;
+
+    if buf == [0xff; GEN_LEN as usize] {
+        return Ok(None);
+    }
+
+    Ok(Some(u32::from_le_bytes(buf)))
+}
+
+fn write_generation<F: NorFlash>(
+    flash: &mut F,
+    half_base: u32,
+    generation: u32,
+) -> Result<(), KvBlobStoreError> {
+    flash
+        .write(half_base, &generation.to_le_bytes())
+        .map_err(|_| KvBlobStoreError::Storage)
+}
+
+/// Whether generation `a` is newer than generation `b` - the usual sequence-number comparison
+/// (newer iff the forward distance from `b` to `a` is less than half the counter's range), so a
+/// single wrap of the `u32` counter still compares correctly.
+fn generation_is_newer(a: u32, b: u32) -> bool {
+    a.wrapping_sub(b) < u32::MAX / 2
+}
+
+/// Computes the CRC32 of `len` bytes starting at `offset`, reading them in small fixed-size
+/// chunks rather than buffering the whole payload - blobs (a fabric certificate, say) can be
+/// larger than is comfortable to put on an MCU's stack.
+fn read_crc32<F: NorFlash>(flash: &mut F, offset: u32, len: u32) -> Result<u32, KvBlobStoreError> {
+    const CHUNK: usize = 32;
+
+    let mut crc = crc32_init();
+    let mut remaining = len;
+    let mut pos = offset;
+    let mut chunk = [0u8; CHUNK];
+
+    while remaining > 0 {
+        let n = remaining.min(CHUNK as u32) as usize;
+        flash
+            .read(pos, &mut chunk[..n])
+            .map_err(|_| KvBlobStoreError::Storage)?;
+
+        crc = crc32_update(crc, &chunk[..n]);
+        pos += n as u32;
+        remaining -= n as u32;
+    }
+
+    Ok(crc32_finish(crc))
+}
+
+fn crc32_init() -> u32 {
+    0xffff_ffff
+}
+
+fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    crc
+}
+
+fn crc32_finish(crc: u32) -> u32 {
+    !crc
+}
+
+fn read_matches_key<F: NorFlash>(
+    flash: &mut F,
+    key_off: u32,
+    key: &str,
+) -> Result<bool, KvBlobStoreError> {
+    let mut buf = [0u8; MAX_KEY_LEN];
+    let key_bytes = key.as_bytes();
+
+    flash
+        .read(key_off, &mut buf[..key_bytes.len()])
+        .map_err(|_| KvBlobStoreError::Storage)?;
+
+    Ok(&buf[..key_bytes.len()] == key_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::vec::Vec;
+
+    use embedded_storage::nor_flash::{
+        ErrorType, NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash,
+    };
+
+    use super::*;
+
+    const HALF_LEN: u32 = 64;
+    const ERASE_SIZE: usize = 16;
+
+    /// An in-memory [`NorFlash`] backed by a shared, `0xFF`-filled byte buffer, so a test can drop
+    /// one [`EspKvBlobStore`] mounted over it and mount a fresh one over the same bytes afterwards
+    /// - simulating a reboot - without the store itself ever exposing a way to hand the flash back.
+    #[derive(Clone)]
+    struct MockFlash(Rc<RefCell<Vec<u8>>>);
+
+    impl MockFlash {
+        fn new(len: usize) -> Self {
+            Self(Rc::new(RefCell::new(std::vec![0xffu8; len])))
+        }
+
+        /// Corrupts `len` bytes starting at `offset`, standing in for a write that was torn by a
+        /// power loss partway through.
+        fn corrupt(&self, offset: u32, len: u32) {
+            let mut data = self.0.borrow_mut();
+            data[offset as usize..(offset + len) as usize].fill(0xaa);
+        }
+    }
+
+    #[derive(Debug)]
+    struct MockFlashError;
+
+    impl NorFlashError for MockFlashError {
+        fn kind(&self) -> NorFlashErrorKind {
+            NorFlashErrorKind::Other
+        }
+    }
+
+    impl ErrorType for MockFlash {
+        type Error = MockFlashError;
+    }
+
+    impl ReadNorFlash for MockFlash {
+        const READ_SIZE: usize = 1;
+
+        fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+            let offset = offset as usize;
+            bytes.copy_from_slice(&self.0.borrow()[offset..offset + bytes.len()]);
+            Ok(())
+        }
+
+        fn capacity(&self) -> usize {
+            self.0.borrow().len()
+        }
+    }
+
+    impl NorFlash for MockFlash {
+        const WRITE_SIZE: usize = 1;
+        const ERASE_SIZE: usize = ERASE_SIZE;
+
+        fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+            self.0.borrow_mut()[from as usize..to as usize].fill(0xff);
+            Ok(())
+        }
+
+        fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+            let offset = offset as usize;
+            self.0.borrow_mut()[offset..offset + bytes.len()].copy_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    /// Every `KvBlobStore` method on `EspKvBlobStore` only ever awaits a `critical_section::with`
+    /// call that completes synchronously - there's no genuine suspension point - so a single poll
+    /// with a no-op waker is enough to drive any of them to completion.
+    fn block_on<F: core::future::Future>(fut: F) -> F::Output {
+        use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+        fn clone(_: *const ()) -> RawWaker {
+            RawWaker::new(core::ptr::null(), &VTABLE)
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+        let waker = unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &VTABLE)) };
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = core::pin::pin!(fut);
+
+        loop {
+            if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                return out;
+            }
+        }
+    }
+
+    fn new_store(flash: MockFlash) -> EspKvBlobStore<MockFlash> {
+        EspKvBlobStore::new(flash, 0, HALF_LEN).unwrap()
+    }
+
+    #[test]
+    fn round_trips_a_stored_value() {
+        let store = new_store(MockFlash::new((HALF_LEN * 2) as usize));
+
+        block_on(store.store("k", b"hello")).unwrap();
+
+        let mut buf = [0u8; 16];
+        let loaded = block_on(store.load("k", &mut buf)).unwrap();
+        assert_eq!(loaded, Some(&b"hello"[..]));
+    }
+
+    #[test]
+    fn remove_deletes_a_value() {
+        let store = new_store(MockFlash::new((HALF_LEN * 2) as usize));
+
+        block_on(store.store("k", b"hello")).unwrap();
+        block_on(store.remove("k")).unwrap();
+
+        let mut buf = [0u8; 16];
+        assert_eq!(block_on(store.load("k", &mut buf)).unwrap(), None);
+    }
+
+    #[test]
+    fn compacts_when_the_active_half_fills_up() {
+        // `records_len` is `HALF_LEN - GEN_LEN` = 60; each 2-byte-key/2-byte-data record is
+        // HEADER_LEN(7) + 2 + 2 + CRC_LEN(4) = 15 bytes, so four fill a half exactly and a fifth
+        // forces `append_record` to compact. The fifth overwrites `k0`, so compaction drops the
+        // superseded `k0` record and the four live records fit the fresh half exactly.
+        let store = new_store(MockFlash::new((HALF_LEN * 2) as usize));
+
+        for i in 0..4 {
+            let key = std::format!("k{i}");
+            block_on(store.store(&key, b"v0")).unwrap();
+        }
+        block_on(store.store("k0", b"v1")).unwrap();
+
+        let mut buf = [0u8; 16];
+        for i in 1..4 {
+            let key = std::format!("k{i}");
+            assert_eq!(
+                block_on(store.load(&key, &mut buf)).unwrap(),
+                Some(&b"v0"[..]),
+                "key {key} should have survived compaction"
+            );
+        }
+        assert_eq!(block_on(store.load("k0", &mut buf)).unwrap(), Some(&b"v1"[..]));
+    }
+
+    #[test]
+    fn remounting_after_a_compaction_picks_the_newer_half() {
+        let flash = MockFlash::new((HALF_LEN * 2) as usize);
+        let store = new_store(flash.clone());
+
+        // Same as `compacts_when_the_active_half_fills_up`: the fifth record forces a flip to the
+        // other half, bumping its generation counter.
+        for i in 0..4 {
+            let key = std::format!("k{i}");
+            block_on(store.store(&key, b"v0")).unwrap();
+        }
+        block_on(store.store("k0", b"v1")).unwrap();
+        drop(store);
+
+        // Simulates a reboot: mount a fresh store over the same (shared) flash bytes and confirm
+        // it resumes from the post-compaction half rather than defaulting back to half 0 (which
+        // still holds the stale `k0 = v0` record).
+        let remounted = new_store(flash);
+
+        let mut buf = [0u8; 16];
+        assert_eq!(
+            block_on(remounted.load("k0", &mut buf)).unwrap(),
+            Some(&b"v1"[..])
+        );
+    }
+
+    #[test]
+    fn a_torn_trailing_write_is_truncated_rather_than_corrupting_later_reads() {
+        let flash = MockFlash::new((HALF_LEN * 2) as usize);
+        let store = new_store(flash.clone());
+
+        block_on(store.store("a", b"1")).unwrap();
+
+        // Stand in for a power loss partway through writing the *next* record: scribble garbage
+        // right after the one genuinely-committed record above, instead of leaving it erased
+        // (`0xFF`) the way a clean append would.
+        let record_len = (HEADER_LEN + 1 /* key */ + 1 /* data */ + CRC_LEN) as u32;
+        flash.corrupt(GEN_LEN + record_len, HEADER_LEN as u32);
+
+        let mut buf = [0u8; 16];
+        // The genuinely-committed record is unaffected...
+        assert_eq!(block_on(store.load("a", &mut buf)).unwrap(), Some(&b"1"[..]));
+
+        // ...and a fresh append lands right after it (overwriting the garbage), not after some
+        // offset that counted the torn bytes as a real record.
+        block_on(store.store("b", b"2")).unwrap();
+        assert_eq!(block_on(store.load("b", &mut buf)).unwrap(), Some(&b"2"[..]));
+        assert_eq!(block_on(store.load("a", &mut buf)).unwrap(), Some(&b"1"[..]));
+    }
+}
@@ -0,0 +1,65 @@
+//! Persistence for the Matter stack's mutable state (fabrics, ACLs, and - for wireless variants -
+//! network credentials).
+
+pub mod esp;
+
+use core::future::Future;
+
+/// A simple key/value blob store the Matter stack persists its state through.
+///
+/// Keys are short, stable, stack-internal identifiers (e.g. `"fabrics"`, `"acls"`,
+/// `"wifi-creds"`); values are opaque, stack-serialized blobs. Implementors only need to get
+/// blobs in and out reliably - the stack owns the serialization format.
+pub trait KvBlobStore {
+    /// Loads the blob previously stored under `key` into `buf`, returning the slice of `buf` that
+    /// was filled in, or `None` if no blob is stored under `key`.
+    fn load<'a>(
+        &self,
+        key: &str,
+        buf: &'a mut [u8],
+    ) -> impl Future<Output = Result<Option<&'a [u8]>, KvBlobStoreError>>;
+
+    /// Stores `data` under `key`, replacing any blob previously stored under that key.
+    fn store(&self, key: &str, data: &[u8]) -> impl Future<Output = Result<(), KvBlobStoreError>>;
+
+    /// Removes the blob stored under `key`, if any.
+    fn remove(&self, key: &str) -> impl Future<Output = Result<(), KvBlobStoreError>>;
+}
+
+/// An error raised by a [`KvBlobStore`] implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KvBlobStoreError {
+    /// The provided buffer was too small to hold the stored blob.
+    BufferTooSmall,
+    /// The underlying storage medium failed.
+    Storage,
+    /// The store ran out of room to persist a new record, even after compaction.
+    Full,
+    /// `data` exceeded the largest blob this store can ever accept, regardless of how much free
+    /// space it has.
+    TooLarge,
+}
+
+/// A [`KvBlobStore`] that does not actually persist anything.
+///
+/// Handy for examples and bring-up: the Matter stack will treat every `load` as a cold start, so
+/// fabrics, ACLs and (where applicable) network credentials do not survive a reboot.
+pub struct DummyKvBlobStore;
+
+impl KvBlobStore for DummyKvBlobStore {
+    async fn load<'a>(
+        &self,
+        _key: &str,
+        _buf: &'a mut [u8],
+    ) -> Result<Option<&'a [u8]>, KvBlobStoreError> {
+        Ok(None)
+    }
+
+    async fn store(&self, _key: &str, _data: &[u8]) -> Result<(), KvBlobStoreError> {
+        Ok(())
+    }
+
+    async fn remove(&self, _key: &str) -> Result<(), KvBlobStoreError> {
+        Ok(())
+    }
+}
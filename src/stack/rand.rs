@@ -0,0 +1,5 @@
+//! Re-export of `rand_core`'s `RngCore`, for users who need to pull random bytes out of a vendor
+//! HAL RNG (e.g. to seed a Matter discriminator) without taking their own dependency on
+//! `rand_core` and risking a version mismatch with ours.
+
+pub use rand_core::RngCore;
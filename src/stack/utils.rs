@@ -0,0 +1,18 @@
+//! Small, stack-internal future helpers that don't belong in any particular subsystem.
+
+pub mod futures {
+    use core::future::Future;
+
+    /// Adapts an infallible `Future<Output = ()>` into one returning `Result<(), E>`, so it can be
+    /// joined with fallible futures in a `select`/[`rs_matter::utils::select::Coalesce`] run loop.
+    pub trait IntoFallible: Future<Output = ()> + Sized {
+        fn into_fallible<E>(self) -> impl Future<Output = Result<(), E>> {
+            async move {
+                self.await;
+                Ok(())
+            }
+        }
+    }
+
+    impl<F> IntoFallible for F where F: Future<Output = ()> {}
+}
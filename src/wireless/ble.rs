@@ -0,0 +1,19 @@
+//! Async BLE HCI transport for Matter commissioning.
+//!
+//! `rs-matter`'s BTP/GATT commissioning path just needs to read and write HCI packets; it does
+//! not care how those packets reach the controller. [`HciTransport`] is that boundary, kept
+//! async so the commissioning future can `.await` it and sleep while idle, rather than
+//! busy-polling the controller on every run-loop iteration (which is what happens if the HCI
+//! read is implemented as a non-blocking `try_read` called in a tight loop).
+
+/// An async source/sink for raw HCI packets (command, event, ACL data - whichever the underlying
+/// controller hands us, tagged with its packet-type byte per the HCI UART transport framing).
+pub trait HciTransport {
+    /// Reads the next available HCI packet into `buf`, resolving only once one is available -
+    /// i.e. this should wake from the controller's own "data available" notification rather than
+    /// being polled on a timer.
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, rs_matter::error::Error>;
+
+    /// Writes `packet` (including its HCI packet-type byte) to the controller.
+    async fn write(&mut self, packet: &[u8]) -> Result<(), rs_matter::error::Error>;
+}
@@ -0,0 +1,107 @@
+//! Concurrent BLE + operational-radio commissioning.
+//!
+//! Matter's preferred commissioning flow keeps the BLE commissioning window (advertising + the
+//! PASE GATT exchange) alive while the device also brings up its operational network (Thread's
+//! 802.15.4 radio, or a WiFi station interface). On silicon where BLE and the operational radio
+//! are two independent radios, this just works. On silicon where they share one radio (as is the
+//! case for `esp-hal`'s IEEE 802.15.4 + BLE today), the two sides cannot literally run at the
+//! same time, so this module provides a cooperative time-division scheduler that hands the radio
+//! back and forth between them in bounded slots, which is indistinguishable from "concurrent" as
+//! far as Matter's commissioning timeouts are concerned.
+
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embassy_sync::signal::Signal;
+use embassy_time::{Duration, Timer};
+
+/// Which side of a shared radio currently holds it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RadioGrant {
+    Ble,
+    Operational,
+}
+
+/// How BLE commissioning and the operational radio (Thread/WiFi) share time, for stacks running
+/// on silicon that cannot run both at once.
+pub enum CommissioningMode<'a> {
+    /// BLE commissioning and the operational radio never run at the same time; commissioning
+    /// must fully hand off to (or fully precede) bringing up the operational network. This is
+    /// what every example in this crate used before concurrent commissioning was added.
+    Sequential,
+    /// BLE commissioning and the operational radio take turns on a shared radio, arbitrated by
+    /// `arbiter`, so that - from Matter's point of view - both stay up for the duration of
+    /// commissioning.
+    Concurrent(&'a RadioArbiter),
+}
+
+impl Default for CommissioningMode<'_> {
+    fn default() -> Self {
+        Self::Sequential
+    }
+}
+
+impl CommissioningMode<'_> {
+    /// Waits for the radio to be available for `side`: immediately in `Sequential` mode (the
+    /// radio is assumed exclusively `side`'s), or once the arbiter grants it in `Concurrent`
+    /// mode.
+    pub(crate) async fn acquire(&self, side: RadioGrant) {
+        if let Self::Concurrent(arbiter) = self {
+            arbiter.acquire(side).await;
+        }
+    }
+
+    /// Drives the arbitration loop in `Concurrent` mode; never resolves in `Sequential` mode,
+    /// since there is nothing to arbitrate.
+    pub(crate) async fn run(&self) {
+        if let Self::Concurrent(arbiter) = self {
+            arbiter.run().await;
+        } else {
+            core::future::pending().await
+        }
+    }
+}
+
+/// Hands a single shared radio back and forth between BLE commissioning and the operational
+/// stack in bounded slots, so that neither side is starved for long enough to drop its session
+/// (BLE losing its GATT link to the commissioner, or the operational stack losing a Thread
+/// attach / WiFi association in progress).
+pub struct RadioArbiter {
+    grant: Signal<NoopRawMutex, RadioGrant>,
+    ble_slot: Duration,
+    operational_slot: Duration,
+}
+
+impl RadioArbiter {
+    /// Creates a new arbiter that grants the radio to BLE for `ble_slot`, then to the
+    /// operational stack for `operational_slot`, repeating for as long as [`Self::run`] is
+    /// polled. Tens of milliseconds per slot is typically enough for a BLE advertising/PASE
+    /// round or a Thread/WiFi radio operation to make progress without starving the other side.
+    pub const fn new(ble_slot: Duration, operational_slot: Duration) -> Self {
+        Self {
+            grant: Signal::new(),
+            ble_slot,
+            operational_slot,
+        }
+    }
+
+    /// Runs the arbitration loop. Drive this concurrently with the BLE and operational radio
+    /// tasks (e.g. via `select3`/`Coalesce`); it never returns.
+    pub async fn run(&self) -> ! {
+        loop {
+            self.grant.signal(RadioGrant::Ble);
+            Timer::after(self.ble_slot).await;
+
+            self.grant.signal(RadioGrant::Operational);
+            Timer::after(self.operational_slot).await;
+        }
+    }
+
+    /// Resolves once the radio is granted to `side`, blocking (cooperatively) while it is held by
+    /// the other side.
+    pub async fn acquire(&self, side: RadioGrant) {
+        loop {
+            if self.grant.wait().await == side {
+                return;
+            }
+        }
+    }
+}
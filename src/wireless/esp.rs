@@ -0,0 +1,336 @@
+//! `esp-hal` / `esp-wifi` wireless drivers.
+
+use embassy_time::{Duration, Timer};
+
+use esp_wifi::ble::controller::BleConnector;
+use esp_wifi::wifi::{ClientConfiguration, Configuration, WifiController, WifiEvent, WifiState};
+use esp_wifi::EspWifiController;
+
+use heapless::Vec;
+
+use crate::stack::persist::{KvBlobStore, KvBlobStoreError};
+
+use super::ble::HciTransport;
+use super::{
+    WifiBand, WifiCommissioningDriver, WifiConnectStatus, WifiCredentials, WifiScanResult,
+    WifiSecurity,
+};
+
+/// Drives an `openthread` radio over `esp-hal`'s IEEE 802.15.4 peripheral.
+///
+/// This does not take the `BT` peripheral - BLE commissioning over the same device is a separate
+/// concern (see [`EspBleHci`]), and holding `BT` here with nothing to do with it would only stop
+/// callers from handing it to an [`EspBleHci`] of their own.
+pub struct EspThreadDriver<'d> {
+    init: &'d EspWifiController<'d>,
+    ieee802154: esp_hal::peripherals::IEEE802154<'d>,
+}
+
+impl<'d> EspThreadDriver<'d> {
+    /// Creates a new `EspThreadDriver` out of the shared `esp-wifi` init handle and the
+    /// IEEE 802.15.4 peripheral it needs exclusive access to.
+    pub fn new(
+        init: &'d EspWifiController<'d>,
+        ieee802154: esp_hal::peripherals::IEEE802154<'d>,
+    ) -> Self {
+        Self { init, ieee802154 }
+    }
+}
+
+const KV_KEY_WIFI_CREDS: &str = "wifi-creds";
+const RECONNECT_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Drives WiFi station mode over `esp-wifi`'s `WifiController`, providing the scanning
+/// (`ScanNetworks`), credential staging (`AddOrUpdateWiFiNetwork`) and connecting
+/// (`ConnectNetwork`) operations of the Matter WiFi Network Commissioning cluster as
+/// [`WifiCommissioningDriver`] methods, and `run` supervises the connection afterwards so device
+/// code no longer has to write its own `connection()` task.
+pub struct EspWifiDriver<'d, S> {
+    controller: WifiController<'d>,
+    store: S,
+    staged: Option<WifiCredentials>,
+    configured: Option<WifiCredentials>,
+}
+
+impl<'d, S> EspWifiDriver<'d, S>
+where
+    S: KvBlobStore,
+{
+    /// Creates a new `EspWifiDriver`, persisting accepted credentials through `store` so the
+    /// device can rejoin its network after a reboot without being re-commissioned.
+    pub fn new(controller: WifiController<'d>, store: S) -> Self {
+        Self {
+            controller,
+            store,
+            staged: None,
+            configured: None,
+        }
+    }
+
+    /// Loads previously-persisted credentials (if any) and stages them, so a subsequent
+    /// [`Self::connect`] - or [`Self::run`]'s own reconnect-on-disconnect loop - rejoins the
+    /// network the device was on before its last reboot.
+    pub async fn load_persisted(&mut self) -> Result<(), rs_matter::error::Error> {
+        let mut buf = [0u8; 128];
+
+        match self.store.load(KV_KEY_WIFI_CREDS, &mut buf).await {
+            Ok(Some(data)) => {
+                if let Some(creds) = decode_credentials(data) {
+                    self.staged = Some(creds);
+                }
+
+                Ok(())
+            }
+            Ok(None) => Ok(()),
+            Err(_) => Err(rs_matter::error::ErrorCode::Invalid.into()),
+        }
+    }
+
+    async fn persist_staged(&self) -> Result<(), rs_matter::error::Error> {
+        let Some(creds) = self.staged.as_ref() else {
+            return Ok(());
+        };
+
+        let mut buf = [0u8; 128];
+        let encoded = encode_credentials(creds, &mut buf);
+
+        self.store
+            .store(KV_KEY_WIFI_CREDS, encoded)
+            .await
+            .map_err(|_| rs_matter::error::ErrorCode::Invalid.into())
+    }
+
+    /// Pushes `creds` to the controller if they differ from whatever it's currently configured
+    /// with, so a re-`stage()` after the controller has already started (re-provisioning onto a
+    /// different network, or a corrected password) actually takes effect instead of silently
+    /// keeping the stale `Configuration` around.
+    ///
+    /// If the controller was already connected under the old configuration, disconnects it so
+    /// the caller's subsequent `connect_async()` reconnects under the new one rather than seeing
+    /// a stale `StaConnected` state and skipping the reconnect entirely.
+    async fn apply_staged_config(
+        &mut self,
+        creds: &WifiCredentials,
+    ) -> Result<(), rs_matter::error::Error> {
+        if self.configured.as_ref() == Some(creds) {
+            return Ok(());
+        }
+
+        let config = Configuration::Client(ClientConfiguration {
+            ssid: creds.ssid.as_str().into(),
+            password: creds.password.as_str().into(),
+            ..Default::default()
+        });
+
+        self.controller
+            .set_configuration(&config)
+            .map_err(|_| rs_matter::error::ErrorCode::Invalid)?;
+        self.configured = Some(creds.clone());
+
+        if matches!(self.controller.is_started(), Ok(true)) {
+            let _ = self.controller.disconnect_async().await;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'d, S> WifiCommissioningDriver for EspWifiDriver<'d, S>
+where
+    S: KvBlobStore,
+{
+    /// Runs the station connection supervision loop: starts the controller if needed, connects
+    /// with the staged credentials, and on disconnect waits out [`RECONNECT_BACKOFF`] before
+    /// retrying - replacing the hand-rolled `connection()` task the non-wireless examples use.
+    async fn run(&mut self) -> Result<(), rs_matter::error::Error> {
+        loop {
+            let Some(creds) = self.staged.clone() else {
+                // Nothing to connect to yet - wait for `AddOrUpdateWiFiNetwork` +
+                // `ConnectNetwork` to stage and trigger a connection.
+                Timer::after(RECONNECT_BACKOFF).await;
+                continue;
+            };
+
+            // Re-pushes the configuration whenever a fresh `stage()` changed it, even if the
+            // controller has already been started from a previous network.
+            self.apply_staged_config(&creds).await?;
+
+            if !matches!(self.controller.is_started(), Ok(true)) {
+                self.controller
+                    .start_async()
+                    .await
+                    .map_err(|_| rs_matter::error::ErrorCode::Invalid)?;
+            }
+
+            if esp_wifi::wifi::wifi_state() != WifiState::StaConnected {
+                match self.controller.connect_async().await {
+                    Ok(()) => self.persist_staged().await?,
+                    Err(_) => {
+                        // Never connected, so `StaDisconnected` below would never fire - back off
+                        // and retry from the top instead of hanging here forever.
+                        Timer::after(RECONNECT_BACKOFF).await;
+                        continue;
+                    }
+                }
+            }
+
+            self.controller
+                .wait_for_event(WifiEvent::StaDisconnected)
+                .await;
+            Timer::after(RECONNECT_BACKOFF).await;
+        }
+    }
+
+    async fn scan(
+        &mut self,
+        results: &mut Vec<WifiScanResult, 16>,
+    ) -> Result<(), rs_matter::error::Error> {
+        let (aps, _count) = self
+            .controller
+            .scan_with_config_async(Default::default())
+            .await
+            .map_err(|_| rs_matter::error::ErrorCode::Invalid)?;
+
+        for ap in aps.into_iter().take(results.capacity()) {
+            let mut ssid = heapless::String::new();
+            let _ = ssid.push_str(ap.ssid.as_str());
+
+            let _ = results.push(WifiScanResult {
+                ssid,
+                bssid: ap.bssid,
+                rssi: ap.signal_strength,
+                channel: ap.channel as u16,
+                band: WifiBand::Ghz2_4,
+                security: auth_method_to_security(ap.auth_method),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn stage(&mut self, credentials: WifiCredentials) {
+        self.staged = Some(credentials);
+    }
+
+    async fn connect(&mut self) -> Result<WifiConnectStatus, rs_matter::error::Error> {
+        let Some(creds) = self.staged.clone() else {
+            return Ok(WifiConnectStatus::NetworkNotFound);
+        };
+
+        // Re-pushes the configuration whenever this `connect()` follows a fresh `stage()` that
+        // changed it, even if the controller was already started and connected to a previous
+        // network.
+        self.apply_staged_config(&creds).await?;
+
+        if !matches!(self.controller.is_started(), Ok(true)) {
+            self.controller
+                .start_async()
+                .await
+                .map_err(|_| rs_matter::error::ErrorCode::Invalid)?;
+        }
+
+        match self.controller.connect_async().await {
+            Ok(_) => {
+                self.persist_staged().await?;
+                Ok(WifiConnectStatus::Success)
+            }
+            Err(esp_wifi::wifi::WifiError::Disconnected) => Ok(WifiConnectStatus::OutOfRange),
+            Err(_) => Ok(WifiConnectStatus::UnknownError),
+        }
+    }
+}
+
+fn auth_method_to_security(auth: esp_wifi::wifi::AuthMethod) -> WifiSecurity {
+    use esp_wifi::wifi::AuthMethod;
+
+    match auth {
+        AuthMethod::None => WifiSecurity::UNENCRYPTED,
+        AuthMethod::WEP => WifiSecurity::WEP,
+        AuthMethod::WPA => WifiSecurity::WPA_PERSONAL,
+        AuthMethod::WPA2Personal | AuthMethod::WPAWPA2Personal => WifiSecurity::WPA2_PERSONAL,
+        AuthMethod::WPA3Personal => WifiSecurity::WPA3_PERSONAL,
+        _ => WifiSecurity::WPA2_PERSONAL,
+    }
+}
+
+/// A minimal, fixed-layout encoding for `WifiCredentials`: this is internal, stack-private state,
+/// not a wire format, so it does not need to be anything fancier than length-prefixed fields.
+fn encode_credentials<'b>(creds: &WifiCredentials, buf: &'b mut [u8; 128]) -> &'b [u8] {
+    let ssid = creds.ssid.as_bytes();
+    let password = creds.password.as_bytes();
+
+    buf[0] = ssid.len() as u8;
+    buf[1..1 + ssid.len()].copy_from_slice(ssid);
+
+    let pw_off = 1 + ssid.len();
+    buf[pw_off] = password.len() as u8;
+    buf[pw_off + 1..pw_off + 1 + password.len()].copy_from_slice(password);
+
+    &buf[..pw_off + 1 + password.len()]
+}
+
+fn decode_credentials(data: &[u8]) -> Option<WifiCredentials> {
+    let ssid_len = *data.first()? as usize;
+    let ssid_bytes = data.get(1..1 + ssid_len)?;
+
+    let pw_off = 1 + ssid_len;
+    let pw_len = *data.get(pw_off)? as usize;
+    let pw_bytes = data.get(pw_off + 1..pw_off + 1 + pw_len)?;
+
+    let mut ssid = heapless::String::new();
+    ssid.push_str(core::str::from_utf8(ssid_bytes).ok()?).ok()?;
+
+    let mut password = heapless::String::new();
+    password
+        .push_str(core::str::from_utf8(pw_bytes).ok()?)
+        .ok()?;
+
+    Some(WifiCredentials { ssid, password })
+}
+
+impl From<KvBlobStoreError> for rs_matter::error::Error {
+    fn from(_: KvBlobStoreError) -> Self {
+        rs_matter::error::ErrorCode::Invalid.into()
+    }
+}
+
+/// An [`HciTransport`] backed by `esp-wifi`'s async `BleConnector`.
+///
+/// `BleConnector`'s `read`/`write` are themselves `.await`-based and wake on the controller's own
+/// "HCI data available" interrupt, so - unlike driving the connector through its non-blocking
+/// `try_read` in a tight loop - this lets the commissioning future actually sleep between HCI
+/// packets instead of busy-polling, which matters most during the open commissioning window
+/// where the device would otherwise never reach a low-power idle state.
+pub struct EspBleHci<'d> {
+    connector: BleConnector<'d>,
+}
+
+impl<'d> EspBleHci<'d> {
+    /// Creates a new `EspBleHci` transport out of the shared `esp-wifi` init handle and the BT
+    /// peripheral.
+    pub fn new(init: &'d EspWifiController<'d>, bt: esp_hal::peripherals::BT<'d>) -> Self {
+        Self {
+            connector: BleConnector::new(init, bt),
+        }
+    }
+}
+
+impl HciTransport for EspBleHci<'_> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, rs_matter::error::Error> {
+        use embedded_io_async::Read;
+
+        self.connector
+            .read(buf)
+            .await
+            .map_err(|_| rs_matter::error::ErrorCode::Invalid.into())
+    }
+
+    async fn write(&mut self, packet: &[u8]) -> Result<(), rs_matter::error::Error> {
+        use embedded_io_async::Write;
+
+        self.connector
+            .write_all(packet)
+            .await
+            .map_err(|_| rs_matter::error::ErrorCode::Invalid.into())
+    }
+}
@@ -0,0 +1,302 @@
+//! Matter stack assemblies for devices that commission and operate over a wireless link the
+//! stack itself must bring up (as opposed to `crate::eth`, where the network is assumed to
+//! already be there).
+//!
+//! Two wireless variants are provided: Thread (`EmbassyThread` / `EmbassyThreadMatterStack`,
+//! commissioning over BLE, operating over 802.15.4) and WiFi (`EmbassyWifi` /
+//! `EmbassyWifiMatterStack`, commissioning over BLE, operating over the station WiFi interface).
+//! Concrete vendor drivers live in the HAL-specific submodules, e.g. [`esp`].
+
+pub mod ble;
+pub mod concurrent;
+pub mod esp;
+
+use heapless::String;
+
+use rs_matter::utils::select::Coalesce;
+
+use crate::stack::persist::KvBlobStore;
+use crate::stack::utils::futures::IntoFallible;
+use crate::stack::{MatterStack, NetRunner};
+
+use self::ble::HciTransport;
+use self::concurrent::{CommissioningMode, RadioGrant};
+
+/// The largest HCI packet (type byte + header + payload) we expect to pump between an
+/// [`HciTransport`] and the commissioning stack in one read.
+const HCI_PACKET_MAX_LEN: usize = 259;
+
+/// Reads and discards HCI packets from `ble` forever, giving an [`HciTransport`] a real call site
+/// to run concurrently with the rest of a transport's `run` loop.
+///
+/// This crate does not yet implement Matter's BTP/GATT commissioning protocol on top of
+/// `HciTransport` - that needs a real BTP session state machine, not a run-loop plumbing fix - so
+/// for now this just keeps the controller's RX queue draining instead of leaving it unread.
+async fn pump_ble(ble: &mut dyn HciTransport) -> Result<(), rs_matter::error::Error> {
+    let mut buf = [0u8; HCI_PACKET_MAX_LEN];
+
+    loop {
+        ble.read(&mut buf).await?;
+    }
+}
+
+/// A Matter stack assembled over Thread (802.15.4), commissioned over BLE.
+pub type EmbassyThreadMatterStack<const N: usize, T = ()> = MatterStack<N, T>;
+
+/// A Matter stack assembled over WiFi station mode, commissioned over BLE.
+pub type EmbassyWifiMatterStack<const N: usize, T = ()> = MatterStack<N, T>;
+
+/// Matter's operational transport over an `openthread` radio driver. It holds the radio grant
+/// (per its [`CommissioningMode`]) and pumps an optional BLE HCI transport; it does not bring up,
+/// join or attach to a Thread network.
+pub struct EmbassyThread<'a, const N: usize, D, T = ()> {
+    driver: D,
+    ieee_eui64: [u8; 8],
+    store: &'a dyn KvBlobStore,
+    stack: &'a MatterStack<N, T>,
+    commissioning_mode: CommissioningMode<'a>,
+    ble: Option<&'a mut dyn HciTransport>,
+}
+
+impl<'a, const N: usize, D, T> EmbassyThread<'a, N, D, T> {
+    /// Creates a new `EmbassyThread` transport, in the (default) [`CommissioningMode::Sequential`]
+    /// - BLE commissioning and the 802.15.4 radio never run at the same time.
+    ///
+    /// `ieee_eui64` seeds the Thread extended address; it only needs to be unique on the Thread
+    /// network the device joins, not globally.
+    pub fn new(
+        driver: D,
+        ieee_eui64: [u8; 8],
+        store: &'a dyn KvBlobStore,
+        stack: &'a MatterStack<N, T>,
+    ) -> Self {
+        Self {
+            driver,
+            ieee_eui64,
+            store,
+            stack,
+            commissioning_mode: CommissioningMode::Sequential,
+            ble: None,
+        }
+    }
+
+    /// Switches this transport to `mode`, e.g. [`CommissioningMode::Concurrent`] on silicon
+    /// that can time-share BLE and the 802.15.4 radio via a [`concurrent::RadioArbiter`].
+    pub fn with_commissioning_mode(mut self, mode: CommissioningMode<'a>) -> Self {
+        self.commissioning_mode = mode;
+        self
+    }
+
+    /// Gives this transport an [`HciTransport`] to pump alongside the radio, so BLE commissioning
+    /// traffic is actually read off the controller while this transport runs.
+    pub fn with_ble_transport(mut self, ble: &'a mut dyn HciTransport) -> Self {
+        self.ble = Some(ble);
+        self
+    }
+}
+
+impl<'a, const N: usize, D, T> NetRunner for EmbassyThread<'a, N, D, T> {
+    /// In [`CommissioningMode::Concurrent`] mode, drives the [`concurrent::RadioArbiter`] for as
+    /// long as this transport runs (it has no other driver, so nothing else will poll it),
+    /// alongside waiting for the radio to be available (per `commissioning_mode`) and - if
+    /// [`Self::with_ble_transport`] was used - pumping BLE HCI traffic.
+    ///
+    /// `arbitrate` is pinned in a local and kept alive across the whole method, rather than
+    /// raced against `acquire` in a single `select` - `acquire` only ever resolves once (the
+    /// first time the radio is granted to the operational side), and dropping `arbitrate` at
+    /// that point would permanently stop handing the radio back to BLE, freezing the round-robin
+    /// in the operational side's favor after its first turn.
+    ///
+    /// Bringing up the `openthread` network itself isn't implemented by this crate yet - this
+    /// holds the radio grant open so a [`CommissioningMode::Concurrent`] arbiter at least sees
+    /// real demand for it, but does not yet join or attach.
+    async fn run(&mut self) -> Result<(), rs_matter::error::Error> {
+        let commissioning_mode = &self.commissioning_mode;
+        let ble = self.ble.as_deref_mut();
+
+        let mut arbitrate = core::pin::pin!(commissioning_mode.run().into_fallible());
+        let mut acquire =
+            core::pin::pin!(commissioning_mode.acquire(RadioGrant::Operational).into_fallible());
+
+        let mut ble_pump = core::pin::pin!(async {
+            if let Some(ble) = ble {
+                pump_ble(ble).await
+            } else {
+                core::future::pending().await
+            }
+        });
+
+        // Wait for the initial operational grant, without ever letting `arbitrate`'s round-robin
+        // loop stop being polled.
+        embassy_futures::select::select3(arbitrate.as_mut(), acquire.as_mut(), ble_pump.as_mut())
+            .coalesce()
+            .await?;
+
+        // `acquire` has now resolved for good (it never re-arms), so only `arbitrate` and
+        // `ble_pump` remain - keep polling both for the rest of this transport's lifetime.
+        embassy_futures::select::select(arbitrate.as_mut(), ble_pump.as_mut())
+            .coalesce()
+            .await?;
+
+        core::future::pending().await
+    }
+}
+
+/// One WiFi access point reported by a [`WifiCommissioningDriver::scan`].
+///
+/// Mirrors the fields the Matter Network Commissioning cluster's `ScanNetworksResponse` wants for
+/// each `WiFiInterfaceScanResult`.
+#[derive(Debug, Clone)]
+pub struct WifiScanResult {
+    pub ssid: String<32>,
+    pub bssid: [u8; 6],
+    pub rssi: i8,
+    pub channel: u16,
+    pub band: WifiBand,
+    pub security: WifiSecurity,
+}
+
+/// The WiFi band an access point was seen on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WifiBand {
+    Ghz2_4,
+    Ghz5,
+    Ghz6,
+}
+
+/// The security mode an access point advertises, as a bitmask (an AP can advertise more than
+/// one, e.g. WPA2/WPA3 mixed mode).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WifiSecurity(pub u8);
+
+impl WifiSecurity {
+    pub const UNENCRYPTED: Self = Self(1 << 0);
+    pub const WEP: Self = Self(1 << 1);
+    pub const WPA_PERSONAL: Self = Self(1 << 2);
+    pub const WPA2_PERSONAL: Self = Self(1 << 3);
+    pub const WPA3_PERSONAL: Self = Self(1 << 4);
+}
+
+/// WiFi credentials as staged by `AddOrUpdateWiFiNetwork`, pending a `ConnectNetwork` command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WifiCredentials {
+    pub ssid: String<32>,
+    pub password: String<64>,
+}
+
+/// The result of a `ConnectNetwork` attempt, using the same status codes the Network
+/// Commissioning cluster reports back to the controller (`NetworkCommissioningStatus`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WifiConnectStatus {
+    Success,
+    OutOfRange,
+    NetworkNotFound,
+    AuthFailure,
+    UnknownError,
+}
+
+/// What a wireless driver needs to implement to back the WiFi Network Commissioning cluster.
+///
+/// The methods map to the cluster's `ScanNetworks` (`scan`), `AddOrUpdateWiFiNetwork` (`stage`)
+/// and `ConnectNetwork` (`connect`) commands, so HAL drivers (see [`esp::EspWifiDriver`]) only
+/// deal with scanning, staging and connecting. `EmbassyWifi` calls only [`Self::run`]; nothing in
+/// this crate dispatches the cluster's commands to `scan`, `stage` or `connect`.
+pub trait WifiCommissioningDriver {
+    /// Runs the station connection (and reconnection) supervision loop. Expected to run forever
+    /// under normal operation - a transient disconnect should be retried internally rather than
+    /// surfaced as an `Err` here.
+    async fn run(&mut self) -> Result<(), rs_matter::error::Error>;
+
+    /// Scans for in-range access points, appending each result to `results` until either the
+    /// scan is exhausted or `results` is full.
+    async fn scan(
+        &mut self,
+        results: &mut heapless::Vec<WifiScanResult, 16>,
+    ) -> Result<(), rs_matter::error::Error>;
+
+    /// Stages `credentials` as the network to join on the next [`Self::connect`], without
+    /// connecting yet (matching `AddOrUpdateWiFiNetwork`, which only takes effect once
+    /// `ConnectNetwork` is invoked or the commissioning window closes with `ConnectNetwork`
+    /// never having been called, in which case the staged credentials are discarded).
+    fn stage(&mut self, credentials: WifiCredentials);
+
+    /// Connects to the staged network (see [`Self::stage`]), returning the connect result Matter
+    /// reports back to the controller.
+    ///
+    /// On success, the credentials are persisted so the device rejoins this network after a
+    /// reboot without requiring re-commissioning.
+    async fn connect(&mut self) -> Result<WifiConnectStatus, rs_matter::error::Error>;
+}
+
+/// Drives the station connection and reconnection supervision of `driver` (its
+/// [`WifiCommissioningDriver::run`]). It does not handle the WiFi Network Commissioning cluster's
+/// commands: the driver's `scan`, `stage` and `connect` are not called from here.
+///
+/// Credential persistence (so the device rejoins its network after a reboot) is the driver's
+/// responsibility - see [`esp::EspWifiDriver`], which is constructed with a [`KvBlobStore`]
+/// of its own for exactly this.
+pub struct EmbassyWifi<'a, const N: usize, D, T = ()> {
+    driver: D,
+    stack: &'a MatterStack<N, T>,
+    commissioning_mode: CommissioningMode<'a>,
+    ble: Option<&'a mut dyn HciTransport>,
+}
+
+impl<'a, const N: usize, D, T> EmbassyWifi<'a, N, D, T>
+where
+    D: WifiCommissioningDriver,
+{
+    /// Creates a new `EmbassyWifi` transport out of `driver`, in the (default)
+    /// [`CommissioningMode::Sequential`] - BLE commissioning and the WiFi station radio never run
+    /// at the same time.
+    pub fn new(driver: D, stack: &'a MatterStack<N, T>) -> Self {
+        Self {
+            driver,
+            stack,
+            commissioning_mode: CommissioningMode::Sequential,
+            ble: None,
+        }
+    }
+
+    /// Switches this transport to `mode`, e.g. [`CommissioningMode::Concurrent`] on silicon
+    /// that can time-share BLE and the WiFi station radio via a [`concurrent::RadioArbiter`].
+    pub fn with_commissioning_mode(mut self, mode: CommissioningMode<'a>) -> Self {
+        self.commissioning_mode = mode;
+        self
+    }
+
+    /// Gives this transport an [`HciTransport`] to pump alongside the station radio, so BLE
+    /// commissioning traffic is actually read off the controller while this transport runs.
+    pub fn with_ble_transport(mut self, ble: &'a mut dyn HciTransport) -> Self {
+        self.ble = Some(ble);
+        self
+    }
+}
+
+impl<'a, const N: usize, D, T> NetRunner for EmbassyWifi<'a, N, D, T>
+where
+    D: WifiCommissioningDriver,
+{
+    /// Runs the WiFi station supervision loop (see [`WifiCommissioningDriver::run`]), alongside -
+    /// in [`CommissioningMode::Concurrent`] mode - driving the [`concurrent::RadioArbiter`] that
+    /// time-shares the radio with BLE commissioning, and - if [`Self::with_ble_transport`] was
+    /// used - pumping BLE HCI traffic.
+    async fn run(&mut self) -> Result<(), rs_matter::error::Error> {
+        let commissioning_mode = &self.commissioning_mode;
+        let ble = self.ble.as_deref_mut();
+
+        let arbitrate = commissioning_mode.run();
+
+        let ble_pump = async {
+            if let Some(ble) = ble {
+                pump_ble(ble).await
+            } else {
+                core::future::pending().await
+            }
+        };
+
+        embassy_futures::select::select3(self.driver.run(), arbitrate.into_fallible(), ble_pump)
+            .coalesce()
+            .await
+    }
+}